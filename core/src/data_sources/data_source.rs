@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Exact Elasticsearch index name data source documents are stored under.
+pub const DATA_SOURCE_INDEX_NAME: &str = "data_sources";
+
+/// Current on-disk schema version for data source documents. Bump this and
+/// add a matching `migrate_vN_to_vN+1` step in
+/// `search_stores::search_types` whenever a field is renamed, defaulted, or
+/// dropped in a way that would break deserializing documents written by an
+/// older indexer.
+pub const DATA_SOURCE_SCHEMA_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataSourceESDocument {
+    pub name: String,
+    /// User-defined synonyms this data source can also be found under,
+    /// indexed alongside `name` so a search for any of them surfaces it.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Schema version this document was written under. Absent on documents
+    /// indexed before versioning existed, which `search_stores::search_types`
+    /// treats as version 0.
+    #[serde(default)]
+    pub schema_version: u64,
+}
+
+impl DataSourceESDocument {
+    /// Builds the document to index for a data source, stamping it with
+    /// the current schema version so it never needs to be migrated on read.
+    pub fn new(name: String, aliases: Vec<String>) -> Self {
+        Self {
+            name,
+            aliases,
+            schema_version: DATA_SOURCE_SCHEMA_VERSION,
+        }
+    }
+}
+
+impl TryFrom<Value> for DataSourceESDocument {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value).map_err(anyhow::Error::from)
+    }
+}
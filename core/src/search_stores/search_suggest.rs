@@ -0,0 +1,178 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::search_stores::search_types::SearchItem;
+
+/// Default number of suggestions returned by `suggest` when the caller has
+/// no specific limit in mind.
+pub const DEFAULT_SUGGESTION_LIMIT: usize = 5;
+
+/// Suggested starting point for `search_with_suggestions`'s `min_score`.
+/// `_score` ranges vary by query shape and analyzer, so callers with a
+/// better-calibrated threshold for their index should pass their own value
+/// instead of relying on this one.
+pub const DEFAULT_MIN_SCORE: f32 = 0.1;
+
+/// A name pulled from the node/data-source indices, paired with the
+/// `SearchItem` it resolves to so a close match can be turned straight into
+/// a suggestion without a second round-trip to Elasticsearch.
+///
+/// Callers are expected to have already restricted the set of candidates to
+/// items sharing a prefix or n-gram with the query (e.g. via an ES `match`
+/// or `terms` query against the name field), so ranking stays cheap even
+/// against large indices.
+pub struct SuggestionCandidate {
+    pub name: String,
+    pub item: SearchItem,
+}
+
+/// Damerau-Levenshtein edit distance between `a` and `b`, normalized by the
+/// length of the longer string so short and long names compete fairly.
+///
+/// Classic DP table: rows are `a`'s characters, columns are `b`'s, each cell
+/// is the min cost of delete/insert/substitute over its neighbors, plus a
+/// transposition case when two adjacent characters are swapped.
+fn normalized_edit_distance(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let longer_len = a.len().max(b.len());
+    if longer_len == 0 {
+        return 0.0;
+    }
+
+    let mut distances = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in distances[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution_cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            distances[i][j] = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + substitution_cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    distances[a.len()][b.len()] as f32 / longer_len as f32
+}
+
+/// Rank `candidates` against `query` by normalized Damerau-Levenshtein
+/// distance and return the `limit` closest matches as `SearchItem`s,
+/// sorted ascending by distance (closest first).
+///
+/// Meant to be called as a fallback when an Elasticsearch query returns no
+/// hits, or only hits scoring below some caller-chosen threshold.
+pub fn suggest(
+    query: &str,
+    candidates: Vec<SuggestionCandidate>,
+    limit: usize,
+) -> Vec<(SearchItem, f32)> {
+    let query = query.to_lowercase();
+
+    let mut scored: Vec<(SearchItem, f32)> = candidates
+        .into_iter()
+        .map(|candidate| {
+            let distance = normalized_edit_distance(&query, &candidate.name.to_lowercase());
+            (candidate.item, distance)
+        })
+        .collect();
+
+    scored.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    scored.truncate(limit);
+
+    scored
+}
+
+/// Outcome of running a search: either the real hits, or — when the query
+/// didn't produce anything worth showing — a set of "did you mean"
+/// suggestions ranked by edit distance against the query.
+pub enum SearchOutcome {
+    Hits(Vec<SearchItem>),
+    Suggestions(Vec<(SearchItem, f32)>),
+}
+
+/// Turns raw Elasticsearch `hits` into a `SearchOutcome`, filtering out
+/// anything scoring below `min_score` and falling back to `suggest` when
+/// nothing is left above it. Pass `DEFAULT_MIN_SCORE` unless the caller's
+/// query/analyzer calls for a different cutoff.
+///
+/// `candidates` is only invoked on the fallback path, so callers should
+/// pass something that lazily fetches candidate names (e.g. a prefix/n-gram
+/// query against the node/data-source indices) rather than eagerly
+/// collecting them on every search.
+pub fn search_with_suggestions(
+    hits: &[Value],
+    query: &str,
+    limit: usize,
+    min_score: f32,
+    candidates: impl FnOnce() -> Vec<SuggestionCandidate>,
+) -> Result<SearchOutcome> {
+    let items = hits
+        .iter()
+        .filter(|hit| {
+            hit.get("_score")
+                .and_then(|score| score.as_f64())
+                .map(|score| score as f32 >= min_score)
+                .unwrap_or(true)
+        })
+        .map(SearchItem::from_hit)
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(if items.is_empty() {
+        SearchOutcome::Suggestions(suggest(query, candidates(), limit))
+    } else {
+        SearchOutcome::Hits(items)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_sources::node::NodeESDocument;
+
+    #[test]
+    fn normalized_edit_distance_identical_strings_is_zero() {
+        assert_eq!(normalized_edit_distance("hello", "hello"), 0.0);
+    }
+
+    #[test]
+    fn normalized_edit_distance_counts_adjacent_transposition_as_one_edit() {
+        // "ab" -> "ba" is a single transposition, not two substitutions.
+        assert_eq!(normalized_edit_distance("ab", "ba"), 0.5);
+    }
+
+    #[test]
+    fn normalized_edit_distance_normalizes_by_the_longer_string() {
+        // "cat" -> "cats" is a single insertion over a length-4 string.
+        assert_eq!(normalized_edit_distance("cat", "cats"), 0.25);
+    }
+
+    fn candidate(name: &str) -> SuggestionCandidate {
+        SuggestionCandidate {
+            name: name.to_string(),
+            item: SearchItem::Node(NodeESDocument::new(name.to_string(), vec![])),
+        }
+    }
+
+    #[test]
+    fn suggest_sorts_ascending_by_distance_and_respects_limit() {
+        let candidates = vec![candidate("widget"), candidate("gidget"), candidate("gadget")];
+
+        let suggestions = suggest("gadget", candidates, 2);
+
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].0.name(), "gadget");
+        assert_eq!(suggestions[0].1, 0.0);
+        assert_eq!(suggestions[1].0.name(), "gidget");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::data_sources::data_source::DataSourceESDocument;
+use crate::data_sources::data_source::{DataSourceESDocument, DATA_SOURCE_INDEX_NAME};
 use anyhow::Result;
 use serde_json::Value;
 
@@ -8,9 +8,119 @@ use crate::data_sources::node::{NodeESDocument, DATA_SOURCE_NODE_INDEX_NAME};
 pub enum SearchItem {
     Node(NodeESDocument),
     DataSource(DataSourceESDocument),
+    /// Matched because the underlying item declared `alias` as one of its
+    /// `aliases` rather than because its primary name matched the query.
+    /// Wraps the resolved item instead of duplicating its fields, so
+    /// callers can render "matched via alias '<alias>'" while still having
+    /// the full item to hand.
+    Alias { alias: String, item: Box<SearchItem> },
 }
 
+/// A document type that can be searched for and deserialized from an
+/// Elasticsearch hit's `_source`, keyed by the exact index it is stored in.
+///
+/// Making a new kind of item searchable takes implementing this for its
+/// document type, a `SearchItem` variant (with its arm in
+/// `SearchItem::name`), and an entry in `SearchItem::registry`;
+/// `SearchItem::from_hit` itself is left untouched.
+trait SearchableDocument: Sized {
+    /// Exact `_index` name this document type is stored under.
+    const INDEX_NAME: &'static str;
+
+    /// Name of this document's highlighted primary-name field (e.g.
+    /// `"title"` or `"name"`), used to tell a direct name match apart from
+    /// an alias match on the same hit.
+    const NAME_FIELD: &'static str;
+
+    fn from_source(source: &Value) -> Result<Self>;
+}
+
+/// In-memory transform applied to a raw `_source` payload to bring it from
+/// one schema version up to the next, before the final typed
+/// deserialization. Each step is only responsible for the rename/default it
+/// introduced, so the chain can be read top to bottom as the document's
+/// history.
+type Migration = fn(Value) -> Value;
+
+/// Reads the `schema_version` field off a raw `_source` payload, treating
+/// its absence as version 0 (documents written before versioning existed).
+fn schema_version(source: &Value) -> u64 {
+    source
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0)
+}
+
+/// Runs `source` through every migration after its detected version, so
+/// `from_hit` can parse documents written by older indexers instead of
+/// panicking or silently dropping fields.
+fn migrate(mut source: Value, version: u64, migrations: &[Migration]) -> Value {
+    for migration in migrations.iter().skip(version as usize) {
+        source = migration(source);
+    }
+    source
+}
+
+/// Entry `i` is the step from version `i` to `i + 1` (see `migrate`).
+/// Node documents at version 0 differ from version 1 only in lacking
+/// `aliases` and `schema_version`, which both fall back to their serde
+/// defaults, so no step is registered yet. The first step added here is
+/// therefore `migrate_v0_to_v1` (an identity step suffices), followed by
+/// `migrate_v1_to_v2` the next time a field is renamed or defaulted.
+const NODE_MIGRATIONS: &[Migration] = &[];
+
+/// See `NODE_MIGRATIONS`.
+const DATA_SOURCE_MIGRATIONS: &[Migration] = &[];
+
+impl SearchableDocument for NodeESDocument {
+    const INDEX_NAME: &'static str = DATA_SOURCE_NODE_INDEX_NAME;
+    const NAME_FIELD: &'static str = "title";
+
+    fn from_source(source: &Value) -> Result<Self> {
+        let version = schema_version(source);
+        let migrated = migrate(source.clone(), version, NODE_MIGRATIONS);
+        NodeESDocument::try_from(migrated)
+    }
+}
+
+impl SearchableDocument for DataSourceESDocument {
+    const INDEX_NAME: &'static str = DATA_SOURCE_INDEX_NAME;
+    const NAME_FIELD: &'static str = "name";
+
+    fn from_source(source: &Value) -> Result<Self> {
+        let version = schema_version(source);
+        let migrated = migrate(source.clone(), version, DATA_SOURCE_MIGRATIONS);
+        DataSourceESDocument::try_from(migrated)
+    }
+}
+
+/// The deserializer half of an entry in the index -> deserializer registry
+/// consulted by `SearchItem::from_hit`.
+type SearchItemBuilder = fn(&Value) -> Result<SearchItem>;
+
+/// One registry entry: the exact index name, the document's highlighted
+/// primary-name field, and the deserializer into a `SearchItem` variant.
+type RegistryEntry = (&'static str, &'static str, SearchItemBuilder);
+
 impl SearchItem {
+    /// Registry of exact index names to the `SearchItem` variant they
+    /// deserialize into. Adding a new searchable document kind requires an
+    /// entry here alongside its `SearchItem` variant.
+    fn registry() -> &'static [RegistryEntry] {
+        &[
+            (
+                NodeESDocument::INDEX_NAME,
+                NodeESDocument::NAME_FIELD,
+                |source| NodeESDocument::from_source(source).map(SearchItem::Node),
+            ),
+            (
+                DataSourceESDocument::INDEX_NAME,
+                DataSourceESDocument::NAME_FIELD,
+                |source| DataSourceESDocument::from_source(source).map(SearchItem::DataSource),
+            ),
+        ]
+    }
+
     pub fn from_hit(hit: &Value) -> Result<Self> {
         let source = hit
             .get("_source")
@@ -21,13 +131,161 @@ impl SearchItem {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing _index"))?;
 
-        // /!\ Very important, must be kept that way since both indices start with the same prefix.
-        if index.starts_with(DATA_SOURCE_NODE_INDEX_NAME) {
-            Ok(SearchItem::Node(NodeESDocument::from(source.clone())))
-        } else {
-            Ok(SearchItem::DataSource(DataSourceESDocument::from(
-                source.clone(),
-            )))
+        let (_, name_field, build) = Self::registry()
+            .iter()
+            .find(|(index_name, _, _)| *index_name == index)
+            .ok_or_else(|| anyhow::anyhow!("Unknown search index: {}", index))?;
+
+        let item = build(source)?;
+
+        Ok(match matched_alias(hit, name_field) {
+            Some(alias) => SearchItem::Alias {
+                alias,
+                item: Box::new(item),
+            },
+            None => item,
+        })
+    }
+
+    /// The primary display/search name of this item, used e.g. by the
+    /// suggestion subsystem to rank it against a user's query.
+    pub fn name(&self) -> &str {
+        match self {
+            SearchItem::Node(node) => &node.title,
+            SearchItem::DataSource(data_source) => &data_source.name,
+            SearchItem::Alias { item, .. } => item.name(),
         }
     }
 }
+
+/// Pulls the alias that actually matched out of an ES hit's highlighted
+/// `aliases` fragments, if the hit matched via an alias rather than the
+/// item's primary name.
+///
+/// `name_field` is the document's highlighted primary-name field (`"title"`
+/// for nodes, `"name"` for data sources). A hit highlighted on that field
+/// matched directly and must not be downgraded to an alias match, even if
+/// `aliases` also happens to be highlighted on the same hit.
+fn matched_alias(hit: &Value, name_field: &str) -> Option<String> {
+    let highlight = hit.get("highlight")?;
+
+    if highlight.get(name_field).is_some() {
+        return None;
+    }
+
+    highlight
+        .get("aliases")
+        .and_then(|fragments| fragments.as_array())
+        .and_then(|fragments| fragments.first())
+        .and_then(|fragment| fragment.as_str())
+        .map(|fragment| fragment.replace("<em>", "").replace("</em>", ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_hit_dispatches_by_exact_index_match_despite_the_shared_prefix() {
+        // `DATA_SOURCE_INDEX_NAME` ("data_sources") is a prefix of
+        // `DATA_SOURCE_NODE_INDEX_NAME` ("data_sources_nodes"). Exact
+        // matching must route each hit correctly regardless.
+        let node_hit = serde_json::json!({
+            "_index": DATA_SOURCE_NODE_INDEX_NAME,
+            "_source": { "title": "A node", "schema_version": 1 },
+        });
+        let data_source_hit = serde_json::json!({
+            "_index": DATA_SOURCE_INDEX_NAME,
+            "_source": { "name": "A data source", "schema_version": 1 },
+        });
+
+        assert!(matches!(
+            SearchItem::from_hit(&node_hit).unwrap(),
+            SearchItem::Node(_)
+        ));
+        assert!(matches!(
+            SearchItem::from_hit(&data_source_hit).unwrap(),
+            SearchItem::DataSource(_)
+        ));
+    }
+
+    #[test]
+    fn from_hit_errors_on_an_unregistered_index() {
+        let hit = serde_json::json!({
+            "_index": "some_other_index",
+            "_source": {},
+        });
+
+        let err = SearchItem::from_hit(&hit).unwrap_err();
+
+        assert!(err.to_string().contains("Unknown search index"));
+    }
+
+    #[test]
+    fn migrate_runs_the_full_chain_from_version_zero() {
+        let migrations: &[Migration] = &[
+            |mut source| {
+                source["v1_field"] = Value::from("added_in_v1");
+                source
+            },
+            |mut source| {
+                source["v2_field"] = Value::from("added_in_v2");
+                source
+            },
+        ];
+
+        let migrated = migrate(serde_json::json!({}), 0, migrations);
+
+        assert_eq!(migrated["v1_field"], Value::from("added_in_v1"));
+        assert_eq!(migrated["v2_field"], Value::from("added_in_v2"));
+    }
+
+    #[test]
+    fn migrate_skips_steps_already_applied_at_the_detected_version() {
+        let migrations: &[Migration] = &[
+            |mut source| {
+                source["v1_field"] = Value::from("added_in_v1");
+                source
+            },
+            |mut source| {
+                source["v2_field"] = Value::from("added_in_v2");
+                source
+            },
+        ];
+
+        // Already at v1: migrate_v0_to_v1 must not rerun.
+        let migrated = migrate(serde_json::json!({}), 1, migrations);
+
+        assert!(migrated.get("v1_field").is_none());
+        assert_eq!(migrated["v2_field"], Value::from("added_in_v2"));
+    }
+
+    #[test]
+    fn from_hit_does_not_downgrade_to_alias_when_the_name_field_is_also_highlighted() {
+        // Regression test for the bug fixed in 86219d6: a hit highlighted
+        // on both its name field and `aliases` is a direct match, not an
+        // alias match.
+        let hit = serde_json::json!({
+            "_index": DATA_SOURCE_NODE_INDEX_NAME,
+            "_source": { "title": "Gadget", "aliases": ["Widget"], "schema_version": 1 },
+            "highlight": { "title": ["<em>Gadget</em>"], "aliases": ["<em>Widget</em>"] },
+        });
+
+        let item = SearchItem::from_hit(&hit).unwrap();
+
+        assert!(matches!(item, SearchItem::Node(_)));
+    }
+
+    #[test]
+    fn from_hit_marks_an_alias_match_when_only_aliases_is_highlighted() {
+        let hit = serde_json::json!({
+            "_index": DATA_SOURCE_NODE_INDEX_NAME,
+            "_source": { "title": "Gadget", "aliases": ["Widget"], "schema_version": 1 },
+            "highlight": { "aliases": ["<em>Widget</em>"] },
+        });
+
+        let item = SearchItem::from_hit(&hit).unwrap();
+
+        assert!(matches!(item, SearchItem::Alias { ref alias, .. } if alias == "Widget"));
+    }
+}